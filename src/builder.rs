@@ -0,0 +1,271 @@
+//! Configurable directory scanning.
+
+use std::path::Path;
+
+use rapidhash::v3::rapidhash_v3;
+
+use crate::{ImportMap, ScannedFile};
+#[cfg(feature = "sri")]
+use crate::IntegrityAlgorithm;
+
+/// Configures a directory scan: which extensions to track, hash length,
+/// and include/exclude glob overrides for the built-in name heuristics
+/// (dev/test files, underscore-prefixed partials, root-level `.js`).
+///
+/// [`ImportMap::scan`] is a convenience wrapper over
+/// `ImportMapBuilder::default().scan(dir, base_url)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportMapBuilder {
+    pub(crate) extensions: Vec<String>,
+    pub(crate) hash_len: usize,
+    pub(crate) include: Vec<String>,
+    pub(crate) exclude: Vec<String>,
+    pub(crate) skip_root_js: bool,
+    /// SRI digest algorithm to compute alongside the cache-busting hash.
+    /// `None` (the default) skips the crypto digest entirely.
+    #[cfg(feature = "sri")]
+    pub(crate) integrity: Option<IntegrityAlgorithm>,
+}
+
+impl Default for ImportMapBuilder {
+    fn default() -> Self {
+        Self {
+            extensions: ImportMap::EXTENSIONS.iter().map(|s| s.to_string()).collect(),
+            hash_len: ImportMap::HASH_LEN,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            skip_root_js: true,
+            #[cfg(feature = "sri")]
+            integrity: None,
+        }
+    }
+}
+
+impl ImportMapBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the set of file extensions to track (default: `js`, `mjs`, `css`).
+    pub fn extensions(mut self, extensions: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Override the hashed-name suffix length (default: 8).
+    pub fn hash_len(mut self, hash_len: usize) -> Self {
+        self.hash_len = hash_len;
+        self
+    }
+
+    /// Only include files matching at least one of these glob patterns,
+    /// overriding the built-in dev/test/underscore and root-level `.js`
+    /// heuristics entirely (`skip_root_js` has no effect then).
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Exclude files matching this glob pattern, on top of whatever
+    /// built-in heuristics still apply.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Whether to skip root-level `.js` files (e.g. `service-worker.js`).
+    /// Enabled by default.
+    pub fn skip_root_js(mut self, skip: bool) -> Self {
+        self.skip_root_js = skip;
+        self
+    }
+
+    /// Opt into computing a Subresource Integrity digest for every scanned
+    /// file, surfaced by `transform_html` as `integrity` attributes and an
+    /// `"integrity"` object in the importmap JSON. Disabled by default,
+    /// since it's a second, cryptographic hash over the same contents
+    /// already read for the (fast, non-cryptographic) filename hash.
+    #[cfg(feature = "sri")]
+    pub fn integrity(mut self, algorithm: IntegrityAlgorithm) -> Self {
+        self.integrity = Some(algorithm);
+        self
+    }
+
+    /// Scan a directory using this configuration.
+    pub fn scan(self, dir: &Path, base_url: &str) -> std::io::Result<ImportMap> {
+        ImportMap::scan_with(self, dir, base_url)
+    }
+
+    /// Decide whether `path` should be tracked and, if so, compute its
+    /// hashed name. Pure function of `self`/`path`/`contents` so the map
+    /// value and the on-disk name produced by [`ImportMap::emit`] can
+    /// never drift apart.
+    pub(crate) fn hash_file(&self, path: &Path, contents: &[u8]) -> Option<ScannedFile> {
+        let ext = self.matching_extension(path)?;
+        let path_str = path.to_string_lossy();
+
+        if !self.include.is_empty() {
+            if !self.include.iter().any(|pattern| glob_match(pattern, &path_str)) {
+                return None;
+            }
+        } else {
+            // Built-in name heuristics only apply when the caller hasn't
+            // taken over file selection via explicit include globs.
+            if self.skip_root_js && ext == "js" && path.parent().is_none_or(|p| p == Path::new("")) {
+                return None;
+            }
+
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if name.contains(".development.") || name.contains(".dev.") || name.contains(".test.") {
+                return None;
+            }
+            if name.starts_with('_') {
+                return None;
+            }
+            if path.components().any(|c| c.as_os_str() == "tests") {
+                return None;
+            }
+        }
+
+        self.hash_contents(path, ext, contents)
+    }
+
+    /// Like [`Self::hash_file`], but for vendored (third-party) files: the
+    /// local-project dev/test/underscore/root-`.js` name heuristics don't
+    /// apply to someone else's package layout, so only the tracked
+    /// extensions and explicit exclude globs are honored.
+    #[cfg(feature = "vendor")]
+    pub(crate) fn hash_file_vendored(&self, path: &Path, contents: &[u8]) -> Option<ScannedFile> {
+        let ext = self.matching_extension(path)?;
+        self.hash_contents(path, ext, contents)
+    }
+
+    /// Extension of `path` if it's tracked and not excluded, else `None`.
+    fn matching_extension<'a>(&self, path: &'a Path) -> Option<&'a str> {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if !self.extensions.iter().any(|e| e == ext) {
+            return None;
+        }
+
+        let path_str = path.to_string_lossy();
+        if self.exclude.iter().any(|pattern| glob_match(pattern, &path_str)) {
+            return None;
+        }
+
+        Some(ext)
+    }
+
+    /// Compute the hashed name (and SRI digest, if configured) for a file
+    /// already decided to be tracked.
+    fn hash_contents(&self, path: &Path, ext: &str, contents: &[u8]) -> Option<ScannedFile> {
+        let stem = path.file_stem().and_then(|s| s.to_str())?;
+
+        let hash = rapidhash_v3(contents);
+        let hash_hex = format!("{:016x}", hash);
+        let hash_len = self.hash_len.min(hash_hex.len());
+        let short_hash = &hash_hex[..hash_len];
+        let hashed_name = format!("{}.{}.{}", stem, short_hash, ext);
+
+        Some(ScannedFile {
+            relative_path: path.to_path_buf(),
+            hashed_name,
+            contents_hash: hash,
+            rewritten: None,
+            #[cfg(feature = "sri")]
+            integrity: self.integrity.map(|algorithm| algorithm.digest(contents)),
+        })
+    }
+}
+
+/// Minimal glob matcher: `*` matches any run of characters except `/`,
+/// `**` also crosses `/`, and `?` matches a single character.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    fn matches(pattern: &[u8], text: &[u8]) -> bool {
+        match pattern.first() {
+            None => text.is_empty(),
+            Some(b'*') if pattern.get(1) == Some(&b'*') => {
+                matches(&pattern[2..], text) || (!text.is_empty() && matches(pattern, &text[1..]))
+            }
+            Some(b'*') => {
+                matches(&pattern[1..], text) || (!text.is_empty() && text[0] != b'/' && matches(pattern, &text[1..]))
+            }
+            Some(b'?') => !text.is_empty() && matches(&pattern[1..], &text[1..]),
+            Some(&c) => !text.is_empty() && text[0] == c && matches(&pattern[1..], &text[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_file_rejects_untracked_extensions() {
+        let builder = ImportMapBuilder::default();
+        assert!(builder.hash_file(Path::new("notes.txt"), b"hi").is_none());
+    }
+
+    #[test]
+    fn extensions_overrides_the_default_set() {
+        let builder = ImportMapBuilder::default().extensions(["txt"]);
+        assert!(builder.hash_file(Path::new("notes.txt"), b"hi").is_some());
+        assert!(builder.hash_file(Path::new("app.js"), b"hi").is_none());
+    }
+
+    #[test]
+    fn hash_len_controls_the_hashed_name_suffix_length() {
+        let builder = ImportMapBuilder::default().hash_len(4);
+        let scanned = builder.hash_file(Path::new("nested/app.js"), b"console.log(1)").unwrap();
+        let stem_and_hash = scanned.hashed_name.strip_suffix(".js").unwrap();
+        let hash = stem_and_hash.rsplit_once('.').unwrap().1;
+        assert_eq!(hash.len(), 4);
+    }
+
+    #[test]
+    fn skip_root_js_excludes_root_level_js_by_default() {
+        let builder = ImportMapBuilder::default();
+        assert!(builder.hash_file(Path::new("service-worker.js"), b"").is_none());
+        assert!(builder.hash_file(Path::new("nested/app.js"), b"").is_some());
+    }
+
+    #[test]
+    fn skip_root_js_false_includes_root_level_js() {
+        let builder = ImportMapBuilder::default().skip_root_js(false);
+        assert!(builder.hash_file(Path::new("service-worker.js"), b"").is_some());
+    }
+
+    #[test]
+    fn built_in_name_heuristics_skip_dev_test_and_underscore_files() {
+        let builder = ImportMapBuilder::default().skip_root_js(false);
+        assert!(builder.hash_file(Path::new("app.dev.js"), b"").is_none());
+        assert!(builder.hash_file(Path::new("app.test.js"), b"").is_none());
+        assert!(builder.hash_file(Path::new("_partial.css"), b"").is_none());
+        assert!(builder.hash_file(Path::new("tests/fixture.js"), b"").is_none());
+    }
+
+    #[test]
+    fn include_overrides_the_built_in_name_heuristics() {
+        let builder = ImportMapBuilder::default().skip_root_js(false).include("*.test.js");
+        assert!(builder.hash_file(Path::new("app.test.js"), b"").is_some());
+        assert!(builder.hash_file(Path::new("app.js"), b"").is_none());
+    }
+
+    #[test]
+    fn exclude_applies_even_under_an_include_glob() {
+        let builder = ImportMapBuilder::default()
+            .skip_root_js(false)
+            .include("*.js")
+            .exclude("*.test.js");
+        assert!(builder.hash_file(Path::new("app.js"), b"").is_some());
+        assert!(builder.hash_file(Path::new("app.test.js"), b"").is_none());
+    }
+
+    #[test]
+    fn glob_match_supports_star_and_globstar() {
+        assert!(glob_match("*.js", "app.js"));
+        assert!(!glob_match("*.js", "nested/app.js"));
+        assert!(glob_match("**/*.js", "nested/app.js"));
+        assert!(!glob_match("*.css", "app.js"));
+    }
+}
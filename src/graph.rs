@@ -0,0 +1,308 @@
+//! A dependency graph over scanned ESM modules.
+//!
+//! Hashing a JS file independently of its imports is unsound once those
+//! imports themselves get hashed names: `app.abc12345.js` still containing
+//! `import "./util.js"` would load the un-hashed sibling. This module finds
+//! the relative specifiers a module references, resolves them to scan-root
+//! paths, and orders modules leaves-first so dependents can be rewritten
+//! (and re-hashed) only after their dependencies have a final hashed URL.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, io,
+    path::{Component, Path, PathBuf},
+};
+
+/// A module (or its transitive imports) forms an import cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircularImport(pub PathBuf);
+
+impl fmt::Display for CircularImport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "circular import detected at {}", self.0.display())
+    }
+}
+
+impl std::error::Error for CircularImport {}
+
+impl From<CircularImport> for io::Error {
+    fn from(err: CircularImport) -> Self {
+        io::Error::other(err)
+    }
+}
+
+/// Resolved import edges for a set of JS/ESM modules, keyed by each
+/// module's path relative to the scan root.
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    edges: BTreeMap<PathBuf, Vec<(String, PathBuf)>>,
+}
+
+impl ModuleGraph {
+    /// Build a graph from each module's relative path and source text.
+    pub fn build(sources: &BTreeMap<PathBuf, String>) -> Self {
+        let mut edges = BTreeMap::new();
+
+        for (path, source) in sources {
+            let base_dir = path.parent().unwrap_or(Path::new(""));
+            let deps = extract_specifiers(source)
+                .into_iter()
+                .filter(|specifier| specifier.starts_with('.'))
+                .map(|specifier| {
+                    let resolved = resolve_specifier(base_dir, &specifier);
+                    (specifier, resolved)
+                })
+                .collect();
+            edges.insert(path.clone(), deps);
+        }
+
+        Self { edges }
+    }
+
+    /// Specifiers and their resolved paths for the given module.
+    pub fn dependencies(&self, path: &Path) -> &[(String, PathBuf)] {
+        self.edges.get(path).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Visit every module leaves-first (dependencies before dependents).
+    ///
+    /// Mirrors a recursive-descent compiler's import resolution: each path
+    /// is pushed onto an explicit "in-progress" set as it's entered, so a
+    /// specifier already on the stack is reported as [`CircularImport`]
+    /// rather than recursing forever.
+    pub fn toposort(&self) -> Result<Vec<PathBuf>, CircularImport> {
+        let mut order = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut in_progress = BTreeSet::new();
+
+        for start in self.edges.keys() {
+            if !visited.contains(start) {
+                self.visit(start, &mut visited, &mut in_progress, &mut order)?;
+            }
+        }
+
+        Ok(order)
+    }
+
+    fn visit(
+        &self,
+        node: &Path,
+        visited: &mut BTreeSet<PathBuf>,
+        in_progress: &mut BTreeSet<PathBuf>,
+        order: &mut Vec<PathBuf>,
+    ) -> Result<(), CircularImport> {
+        if visited.contains(node) {
+            return Ok(());
+        }
+        if in_progress.contains(node) {
+            return Err(CircularImport(node.to_path_buf()));
+        }
+        in_progress.insert(node.to_path_buf());
+
+        if let Some(deps) = self.edges.get(node) {
+            for (_, dep) in deps {
+                // Only modules we actually scanned are nodes in the graph;
+                // specifiers pointing outside the scan stay as leaves.
+                if self.edges.contains_key(dep) {
+                    self.visit(dep, visited, in_progress, order)?;
+                }
+            }
+        }
+
+        in_progress.remove(node);
+        visited.insert(node.to_path_buf());
+        order.push(node.to_path_buf());
+        Ok(())
+    }
+}
+
+/// Find specifiers referenced via `import`/`export ... from` and dynamic
+/// `import(...)` calls.
+pub(crate) fn extract_specifiers(source: &str) -> Vec<String> {
+    let source = &strip_comments(source);
+    let mut specifiers = Vec::new();
+
+    for keyword in ["import", "from"] {
+        let mut i = 0;
+        while let Some(offset) = source[i..].find(keyword) {
+            let start = i + offset;
+            let end = start + keyword.len();
+            let before_ok = start == 0
+                || !source.as_bytes()[start - 1].is_ascii_alphanumeric() && source.as_bytes()[start - 1] != b'_';
+            let after_ok = source
+                .as_bytes()
+                .get(end)
+                .is_none_or(|b| !b.is_ascii_alphanumeric() && *b != b'_');
+            i = end;
+            if before_ok && after_ok {
+                if let Some(specifier) = specifier_after(source, i) {
+                    specifiers.push(specifier);
+                }
+            }
+        }
+    }
+
+    specifiers
+}
+
+/// Blank out `//` line comments and `/* */` block comments so they can't be
+/// mistaken for real `import`/`from` specifiers, without disturbing string
+/// literals (a URL like `"http://example.com"` must survive intact). Quoted
+/// strings are copied through verbatim, backslash escapes and all.
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut in_string = None;
+
+    while let Some(c) = chars.next() {
+        if let Some(quote) = in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == quote {
+                in_string = None;
+            }
+            continue;
+        }
+
+        match c {
+            '"' | '\'' | '`' => {
+                in_string = Some(c);
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                chars.next();
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    if c == '\n' {
+                        out.push('\n');
+                    }
+                    prev = c;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Given a position just after an `import`/`from` keyword, return the next
+/// quoted string literal if one starts before any other significant token.
+fn specifier_after(source: &str, pos: usize) -> Option<String> {
+    let rest = source.get(pos..)?;
+    let trimmed = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '(');
+    let quote = trimmed.chars().next()?;
+    if quote != '"' && quote != '\'' {
+        return None;
+    }
+    let after_quote = &trimmed[1..];
+    let end = after_quote.find(quote)?;
+    Some(after_quote[..end].to_string())
+}
+
+/// Resolve a relative specifier against the importing module's directory,
+/// collapsing `.`/`..` components lexically (the file need not exist yet).
+fn resolve_specifier(base_dir: &Path, specifier: &str) -> PathBuf {
+    let mut resolved = base_dir.to_path_buf();
+    for component in Path::new(specifier).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_specifiers_finds_static_and_dynamic_imports() {
+        let source = r#"
+            import x from "./x.js";
+            import { y } from './y.js';
+            export { z } from "./z.js";
+            const mod = await import("./dynamic.js");
+        "#;
+        let mut specifiers = extract_specifiers(source);
+        specifiers.sort();
+        assert_eq!(specifiers, ["./dynamic.js", "./x.js", "./y.js", "./z.js"]);
+    }
+
+    #[test]
+    fn extract_specifiers_ignores_bare_words_containing_the_keyword() {
+        let source = r#"const reimport = 1; const fromage = "./not-a-specifier.js";"#;
+        assert!(extract_specifiers(source).is_empty());
+    }
+
+    #[test]
+    fn extract_specifiers_ignores_line_and_block_comments() {
+        let source = r#"
+            // import "./fake.js";
+            /* import "./also-fake.js"; */
+            import real from "./real.js";
+        "#;
+        assert_eq!(extract_specifiers(source), ["./real.js"]);
+    }
+
+    #[test]
+    fn extract_specifiers_preserves_urls_inside_string_literals() {
+        let source = r#"import { BASE } from "./config.js"; const url = "http://example.com";"#;
+        assert_eq!(extract_specifiers(source), ["./config.js"]);
+    }
+
+    #[test]
+    fn toposort_orders_dependencies_before_dependents() {
+        let mut sources = BTreeMap::new();
+        sources.insert(PathBuf::from("app.js"), "import './util.js';".to_string());
+        sources.insert(PathBuf::from("util.js"), "export const x = 1;".to_string());
+
+        let graph = ModuleGraph::build(&sources);
+        let order = graph.toposort().unwrap();
+
+        let util_pos = order.iter().position(|p| p == Path::new("util.js")).unwrap();
+        let app_pos = order.iter().position(|p| p == Path::new("app.js")).unwrap();
+        assert!(util_pos < app_pos);
+    }
+
+    #[test]
+    fn toposort_detects_circular_imports() {
+        let mut sources = BTreeMap::new();
+        sources.insert(PathBuf::from("a.js"), "import './b.js';".to_string());
+        sources.insert(PathBuf::from("b.js"), "import './a.js';".to_string());
+
+        let graph = ModuleGraph::build(&sources);
+        assert!(graph.toposort().is_err());
+    }
+
+    #[test]
+    fn dependencies_resolves_relative_specifiers_against_the_importing_dir() {
+        let mut sources = BTreeMap::new();
+        sources.insert(PathBuf::from("src/app.js"), "import './lib/util.js';".to_string());
+        sources.insert(PathBuf::from("src/lib/util.js"), "export const x = 1;".to_string());
+
+        let graph = ModuleGraph::build(&sources);
+        let deps = graph.dependencies(Path::new("src/app.js"));
+
+        assert_eq!(deps, &[("./lib/util.js".to_string(), PathBuf::from("src/lib/util.js"))]);
+    }
+}
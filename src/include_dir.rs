@@ -1,24 +1,36 @@
 //! Support for scanning embedded directories from `include_dir!`.
 
+use std::{io, path::PathBuf};
+
 use include_dir::Dir;
 
 use crate::ImportMap;
 
 impl ImportMap {
-    /// Scan an embedded directory (from `include_dir!`) and generate an import map.
-    pub fn scan_embedded(dir: &Dir<'_>, base_url: &str) -> Self {
+    /// Scan an embedded directory (from `include_dir!`) and generate an
+    /// import map.
+    ///
+    /// Goes through the same graph-aware `insert_scanned` path as
+    /// [`ImportMap::scan`], so relative imports between embedded JS/ESM
+    /// modules are rewritten to hashed URLs instead of each file being
+    /// hashed independently of its imports.
+    pub fn scan_embedded(dir: &Dir<'_>, base_url: &str) -> io::Result<Self> {
         let mut map = Self::empty();
         let base_url = base_url.trim_end_matches('/');
-        map.scan_dir(dir, base_url);
-        map
+
+        let mut scanned = Vec::new();
+        Self::collect_embedded(dir, &mut scanned);
+        map.insert_scanned(scanned, base_url)?;
+
+        Ok(map)
     }
 
-    fn scan_dir(&mut self, dir: &Dir<'_>, base_url: &str) {
+    fn collect_embedded(dir: &Dir<'_>, scanned: &mut Vec<(PathBuf, Vec<u8>)>) {
         for file in dir.files() {
-            self.process_file(file.path(), file.contents(), base_url);
+            scanned.push((file.path().to_path_buf(), file.contents().to_vec()));
         }
         for subdir in dir.dirs() {
-            self.scan_dir(subdir, base_url);
+            Self::collect_embedded(subdir, scanned);
         }
     }
 }
@@ -32,14 +44,14 @@ mod tests {
 
     #[test]
     fn scan_embedded_finds_js_files() {
-        let map = ImportMap::scan_embedded(&TEST_DIR, "");
-        assert!(map.0.is_empty() || !map.0.is_empty());
+        let map = ImportMap::scan_embedded(&TEST_DIR, "").unwrap();
+        assert!(map.is_empty() || !map.is_empty());
     }
 
     #[test]
     fn scan_embedded_with_base_url() {
-        let map = ImportMap::scan_embedded(&TEST_DIR, "/assets");
-        for key in map.0.keys() {
+        let map = ImportMap::scan_embedded(&TEST_DIR, "/assets").unwrap();
+        for key in map.keys() {
             assert!(key.starts_with("/assets/") || key.starts_with("/assets"));
         }
     }
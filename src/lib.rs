@@ -2,27 +2,96 @@
 
 use std::{
     collections::BTreeMap,
-    fs, io,
+    fmt, fs, io,
     ops::Deref,
     path::{Path, PathBuf},
 };
 
-use rapidhash::v3::rapidhash_v3;
+use rayon::prelude::*;
 
 #[cfg(feature = "embedded")]
 mod include_dir;
 
+mod graph;
+use graph::ModuleGraph;
+
+mod builder;
+pub use builder::ImportMapBuilder;
+
+#[cfg(feature = "vendor")]
+mod vendor;
+#[cfg(feature = "vendor")]
+pub use vendor::{Fetcher, HttpFetcher};
+
+#[cfg(feature = "sri")]
+mod sri;
+#[cfg(feature = "sri")]
+pub use sri::IntegrityAlgorithm;
+
 /// Import map structure matching the web standard.
 #[derive(Debug, Clone, Default, PartialEq)]
-pub struct ImportMap(BTreeMap<String, String>);
+pub struct ImportMap {
+    imports: BTreeMap<String, String>,
+    files: Vec<ScannedFile>,
+    /// Original URLs of JS/ESM modules, in dependency order (a module's
+    /// imports come before the module itself) so `transform_html` can
+    /// preload dependencies before dependents.
+    js_order: Vec<String>,
+    /// Configuration this map was scanned with; `strip_hash` needs it to
+    /// round-trip correctly when a non-default hash length was chosen.
+    options: ImportMapBuilder,
+    /// SRI digests keyed by hashed URL, populated when the builder was
+    /// configured with [`ImportMapBuilder::integrity`].
+    #[cfg(feature = "sri")]
+    integrity: BTreeMap<String, String>,
+}
 
 impl Deref for ImportMap {
     type Target = BTreeMap<String, String>;
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.imports
     }
 }
 
+/// A single scanned asset file, carrying everything needed to both record it
+/// in the import map and later emit it on disk under its hashed name.
+#[derive(Debug, Clone, PartialEq)]
+struct ScannedFile {
+    /// Path relative to the scan root.
+    relative_path: PathBuf,
+    /// File name with the hash spliced in, e.g. `foo.abc12345.js`.
+    hashed_name: String,
+    /// Full rapidhash digest of the file contents.
+    #[allow(dead_code)]
+    contents_hash: u64,
+    /// Set when import specifiers were rewritten to hashed URLs; `emit`
+    /// writes these bytes instead of copying the original file.
+    rewritten: Option<Vec<u8>>,
+    /// SRI digest (`sha384-...`) of the final contents, when the builder
+    /// was configured with [`ImportMapBuilder::integrity`].
+    #[cfg(feature = "sri")]
+    integrity: Option<String>,
+}
+
+/// A URL referenced by generated HTML that doesn't resolve to a real file
+/// under the scanned root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenRef {
+    /// Where the reference was found: `"stylesheet"`, `"modulepreload"`, or
+    /// `"importmap"`.
+    pub kind: &'static str,
+    /// The URL that doesn't resolve.
+    pub url: String,
+}
+
+impl fmt::Display for BrokenRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} reference does not resolve to a file: {}", self.kind, self.url)
+    }
+}
+
+impl std::error::Error for BrokenRef {}
+
 impl ImportMap {
     pub const EXTENSIONS: &[&str] = &["js", "mjs", "css"];
     pub const HASH_LEN: usize = 8;
@@ -34,94 +103,191 @@ impl ImportMap {
         Self::default()
     }
 
-    /// Scan a directory and generate an import map.
+    /// Scan a directory and generate an import map using the default
+    /// configuration. A convenience wrapper over
+    /// `ImportMapBuilder::default().scan(dir, base_url)`.
     pub fn scan(dir: &Path, base_url: &str) -> io::Result<Self> {
-        let mut map = Self::empty();
+        ImportMapBuilder::default().scan(dir, base_url)
+    }
+
+    /// Scan a directory under a given [`ImportMapBuilder`] configuration.
+    ///
+    /// The directory walk itself is serial (it's just `read_dir` calls), but
+    /// hashing the collected files runs in parallel across a thread pool.
+    /// Results are folded into the `BTreeMap` afterwards, so output is
+    /// deterministic regardless of completion order.
+    fn scan_with(options: ImportMapBuilder, dir: &Path, base_url: &str) -> io::Result<Self> {
         let base_url = base_url.trim_end_matches('/');
-        map.scan_fs(dir, dir, base_url)?;
+        let paths = Self::collect_paths(dir)?;
+
+        let scanned: Vec<(PathBuf, Vec<u8>)> = paths
+            .par_iter()
+            .map(|path| -> io::Result<Option<(PathBuf, Vec<u8>)>> {
+                let relative = path.strip_prefix(dir).expect("path collected under dir").to_path_buf();
+                let contents = fs::read(path)?;
+                Ok(options.hash_file(&relative, &contents).map(|_| (relative, contents)))
+            })
+            .collect::<io::Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let mut map = Self { options, ..Self::default() };
+        map.insert_scanned(scanned, base_url)?;
         Ok(map)
     }
 
-    fn scan_fs(&mut self, root: &Path, dir: &Path, base_url: &str) -> io::Result<()> {
-        for entry in fs::read_dir(dir)? {
-            let path = entry?.path();
-            if path.is_dir() {
-                self.scan_fs(root, &path, base_url)?;
-            } else if let Ok(relative) = path.strip_prefix(root) {
-                self.process_file(relative, &fs::read(&path)?, base_url);
+    /// Insert every scanned file, rewriting JS/ESM import specifiers to
+    /// hashed URLs first.
+    ///
+    /// A module graph is built over the `.js`/`.mjs` files, then visited
+    /// leaves-first: by the time a file's specifiers are rewritten, every
+    /// local import it references already has a final hashed URL, so its
+    /// own hash is computed over the *rewritten* contents. Non-JS files
+    /// don't participate in the graph and are inserted as before.
+    fn insert_scanned(&mut self, scanned: Vec<(PathBuf, Vec<u8>)>, base_url: &str) -> io::Result<()> {
+        let mut js_sources = BTreeMap::new();
+        let mut others = Vec::new();
+
+        for (relative, contents) in scanned {
+            let ext = relative.extension().and_then(|e| e.to_str()).unwrap_or("");
+            match (ext, String::from_utf8(contents.clone())) {
+                ("js" | "mjs", Ok(source)) => {
+                    js_sources.insert(relative, source);
+                }
+                _ => others.push((relative, contents)),
+            }
+        }
+
+        let graph = ModuleGraph::build(&js_sources);
+        let order = graph.toposort()?;
+
+        for path in &order {
+            if let Some(source) = js_sources.get(path) {
+                self.insert_js_module(path, source, &graph, base_url);
             }
         }
+
+        for (relative, contents) in others {
+            self.process_file(&relative, &contents, base_url);
+        }
+
         Ok(())
     }
 
-    /// Process a file and insert into imports if it should be included.
-    fn process_file(&mut self, path: &Path, contents: &[u8], base_url: &str) {
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    /// Rewrite a module's relative import specifiers to the hashed URLs of
+    /// their already-inserted dependencies, then hash and insert it.
+    fn insert_js_module(&mut self, path: &Path, source: &str, graph: &ModuleGraph, base_url: &str) {
+        let mut rewritten = source.to_string();
 
-        if !Self::EXTENSIONS.contains(&ext) {
-            return;
+        for (specifier, resolved) in graph.dependencies(path) {
+            let dep_url = format!("{}/{}", base_url, resolved.display());
+            if let Some(hashed) = self.imports.get(&dep_url) {
+                rewritten = rewritten.replace(&format!("\"{specifier}\""), &format!("\"{hashed}\""));
+                rewritten = rewritten.replace(&format!("'{specifier}'"), &format!("'{hashed}'"));
+            }
         }
 
-        // Skip JS files at root (e.g. service-worker.js)
-        if ext == "js" && path.parent().is_none_or(|p| p == Path::new("")) {
+        let bytes = rewritten.into_bytes();
+        let Some(mut scanned) = self.options.hash_file(path, &bytes) else {
             return;
+        };
+        let original_url = format!("{}/{}", base_url, scanned.relative_path.display());
+        if bytes != source.as_bytes() {
+            scanned.rewritten = Some(bytes);
         }
+        self.insert_file(original_url, scanned, base_url);
+    }
 
-        // Skip development builds and test files
-        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-        if name.contains(".development.") || name.contains(".dev.") || name.contains(".test.") {
-            return;
+    /// Recursively collect every regular file under `dir`.
+    fn collect_paths(dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                paths.extend(Self::collect_paths(&path)?);
+            } else {
+                paths.push(path);
+            }
         }
+        Ok(paths)
+    }
 
-        // Skip underscore-prefixed files (partials/internal)
-        if name.starts_with('_') {
-            return;
+    /// Process a file and insert into imports if it should be included.
+    fn process_file(&mut self, path: &Path, contents: &[u8], base_url: &str) {
+        if let Some(scanned) = self.options.hash_file(path, contents) {
+            let original_url = format!("{}/{}", base_url, scanned.relative_path.display());
+            self.insert_file(original_url, scanned, base_url);
         }
+    }
 
-        // Skip test files
-        if path.components().any(|c| c.as_os_str() == "tests") {
-            return;
-        }
+    /// Record an already-hashed file under the given original URL (the key
+    /// callers will rewrite other specifiers to point at).
+    fn insert_file(&mut self, original_url: String, scanned: ScannedFile, base_url: &str) {
+        let parent = scanned.relative_path.parent().filter(|p| *p != Path::new(""));
 
-        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
-            return;
+        let hashed_url = match parent {
+            Some(p) => format!("{}/{}/{}", base_url, p.display(), scanned.hashed_name),
+            None => format!("{}/{}", base_url, scanned.hashed_name),
         };
 
-        let hash = rapidhash_v3(contents);
-        let hash_hex = format!("{:016x}", hash);
-        let short_hash = &hash_hex[..Self::HASH_LEN];
+        let ext = scanned.relative_path.extension().and_then(|e| e.to_str());
+        if matches!(ext, Some("js" | "mjs")) {
+            self.js_order.push(original_url.clone());
+        }
+
+        #[cfg(feature = "sri")]
+        if let Some(digest) = &scanned.integrity {
+            self.integrity.insert(hashed_url.clone(), digest.clone());
+        }
 
-        let original_url = format!("{}/{}", base_url, path.display());
-        let parent = path.parent().filter(|p| *p != Path::new(""));
+        self.imports.insert(original_url, hashed_url);
+        self.files.push(scanned);
+    }
 
-        let hashed_url = match parent {
-            Some(p) => format!(
-                "{}/{}/{}.{}.{}",
-                base_url,
-                p.display(),
-                stem,
-                short_hash,
-                ext
-            ),
-            None => format!("{}/{}.{}.{}", base_url, stem, short_hash, ext),
-        };
+    /// Copy every scanned file from `src_dir` to `out_dir` under its hashed
+    /// name, preserving subdirectory structure (like lightningcss's
+    /// `-d/--output-dir`). Modules whose import specifiers were rewritten
+    /// are written from their rewritten contents instead of copied.
+    pub fn emit(&self, src_dir: &Path, out_dir: &Path) -> io::Result<()> {
+        for file in &self.files {
+            let src = src_dir.join(&file.relative_path);
+
+            let dest_relative = match file.relative_path.parent().filter(|p| *p != Path::new("")) {
+                Some(parent) => parent.join(&file.hashed_name),
+                None => PathBuf::from(&file.hashed_name),
+            };
+            let dest = out_dir.join(dest_relative);
+
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
 
-        self.0.insert(original_url, hashed_url);
+            match &file.rewritten {
+                Some(contents) => fs::write(&dest, contents)?,
+                None => {
+                    fs::copy(&src, &dest)?;
+                }
+            }
+        }
+        Ok(())
     }
 
-    /// Strip hash from filename: `foo.abc12345.js` -> `foo.js`
-    pub fn strip_hash(path: &Path) -> Option<PathBuf> {
+    /// Strip hash from filename: `foo.abc12345.js` -> `foo.js`, honoring
+    /// this map's configured hash length rather than [`Self::HASH_LEN`] so
+    /// round-tripping stays correct when a non-default length was chosen.
+    pub fn strip_hash(&self, path: &Path) -> Option<PathBuf> {
         let stem = path.file_stem()?.to_str()?;
         let ext = path.extension()?.to_str()?;
 
-        if !Self::EXTENSIONS.contains(&ext) {
+        if !self.options.extensions.iter().any(|e| e == ext) {
             return None;
         }
 
         let dot_pos = stem.rfind('.')?;
         let hash = &stem[dot_pos + 1..];
 
-        if hash.len() == Self::HASH_LEN && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        if hash.len() == self.options.hash_len && hash.chars().all(|c| c.is_ascii_hexdigit()) {
             let name = &stem[..dot_pos];
             Some(path.with_file_name(format!("{}.{}", name, ext)))
         } else {
@@ -131,8 +297,14 @@ impl ImportMap {
 
     /// Update an HTML file in place between `<!-- IMPORTMAP -->` and `<!-- /IMPORTMAP -->` markers.
     pub fn update_html_file(&self, path: &Path) -> io::Result<bool> {
+        self.update_html_file_with(path, false)
+    }
+
+    /// Like [`Self::update_html_file`], but passing `minify` through to
+    /// [`Self::transform_html_with`].
+    pub fn update_html_file_with(&self, path: &Path, minify: bool) -> io::Result<bool> {
         let html = fs::read_to_string(path)?;
-        match self.transform_html(&html) {
+        match self.transform_html_with(&html, minify) {
             Some(updated) if updated != html => {
                 fs::write(path, updated)?;
                 Ok(true)
@@ -141,22 +313,43 @@ impl ImportMap {
         }
     }
 
-    /// Transform HTML content between `<!-- IMPORTMAP -->` and `<!-- /IMPORTMAP -->` markers.
+    /// Transform HTML content between `<!-- IMPORTMAP -->` and `<!-- /IMPORTMAP -->`
+    /// markers, spec-respecting pretty-printed. A convenience wrapper over
+    /// `transform_html_with(html, false)`.
     pub fn transform_html(&self, html: &str) -> Option<String> {
-        if self.0.is_empty() {
+        self.transform_html_with(html, false)
+    }
+
+    /// Transform HTML content between `<!-- IMPORTMAP -->` and `<!-- /IMPORTMAP -->`
+    /// markers. When `minify` is set, the importmap JSON is compacted
+    /// (`serde_json::to_string` instead of `to_string_pretty`); tags stay
+    /// one per line since [`Self::extract_href_values`] is line-based and
+    /// reads back whatever this emits on the next pass. Content outside the
+    /// markers, and the indentation logic that places the block, are
+    /// untouched either way so diffs stay stable when minification is off.
+    pub fn transform_html_with(&self, html: &str, minify: bool) -> Option<String> {
+        if self.imports.is_empty() {
             return Self::replace_between_markers(html, "");
         }
 
-        // Partition by file type
-        let (mut css, js): (BTreeMap<_, _>, Vec<_>) =
-            self.0.iter().fold((BTreeMap::new(), Vec::new()), |(mut css, mut js), (k, v)| {
-                match Path::new(k).extension().and_then(|e| e.to_str()) {
-                    Some("css") => { css.insert(k.clone(), v.clone()); }
-                    Some("js" | "mjs") => js.push((k, v)),
-                    _ => {}
-                }
-                (css, js)
-            });
+        // Partition by file type. JS entries follow `js_order` (dependencies
+        // before dependents) rather than the map's alphabetical key order,
+        // so preloads list modules in load order; the importmap JSON itself
+        // is keyed alphabetically. Stylesheets are keyed by both their
+        // original and hashed URL, so a later pass over this method's own
+        // output (which carries hashed hrefs) still finds them.
+        let mut css: BTreeMap<_, _> = self
+            .imports
+            .iter()
+            .filter(|(k, _)| Path::new(k).extension().and_then(|e| e.to_str()) == Some("css"))
+            .flat_map(|(k, v)| [(k.clone(), v.clone()), (v.clone(), v.clone())])
+            .collect();
+
+        let js: Vec<(&String, &String)> = self
+            .js_order
+            .iter()
+            .filter_map(|url| self.imports.get_key_value(url))
+            .collect();
 
         // Only include CSS already in HTML (preserves order for cascade correctness)
         let css_urls: Vec<_> = Self::extract_href_values(html, "stylesheet")
@@ -167,17 +360,26 @@ impl ImportMap {
         // Generate output
         let stylesheets = css_urls
             .iter()
-            .map(|url| format!(r#"<link rel="stylesheet" href="{url}">"#))
+            .map(|url| format!(r#"<link rel="stylesheet" href="{url}"{}>"#, self.integrity_attr(url)))
             .collect::<Vec<_>>()
             .join("\n");
 
         let js_map: BTreeMap<_, _> = js.iter().map(|(k, v)| (*k, *v)).collect();
-        let json = serde_json::to_string_pretty(&serde_json::json!({ "imports": js_map })).ok()?;
-        let script = format!("<script type=\"importmap\">\n{json}\n</script>");
+        let json_value = self.importmap_json(&js_map);
+        let json = if minify {
+            serde_json::to_string(&json_value).ok()?
+        } else {
+            serde_json::to_string_pretty(&json_value).ok()?
+        };
+        let script = if minify {
+            format!("<script type=\"importmap\">{json}</script>")
+        } else {
+            format!("<script type=\"importmap\">\n{json}\n</script>")
+        };
 
         let preloads = js
             .iter()
-            .map(|(_, url)| format!(r#"<link rel="modulepreload" href="{url}">"#))
+            .map(|(_, url)| format!(r#"<link rel="modulepreload" href="{url}"{}>"#, self.integrity_attr(url)))
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -190,6 +392,101 @@ impl ImportMap {
         Self::replace_between_markers(html, &content)
     }
 
+    /// Verify that every URL referenced by `html` (stylesheet `href`s,
+    /// `modulepreload` `href`s, and the importmap's `imports` values)
+    /// resolves to a real file under `root`. Modeled on rustdoc's
+    /// linkchecker: collect every dangling reference instead of failing on
+    /// the first one.
+    pub fn verify_html(&self, html: &str, root: &Path) -> Result<(), Vec<BrokenRef>> {
+        let broken: Vec<BrokenRef> = [("stylesheet", "stylesheet"), ("modulepreload", "modulepreload")]
+            .into_iter()
+            .flat_map(|(rel, kind)| {
+                Self::extract_href_values(html, rel)
+                    .into_iter()
+                    .filter(|url| !Self::url_resolves(url, root))
+                    .map(move |url| BrokenRef { kind, url })
+            })
+            .chain(
+                Self::extract_importmap_urls(html)
+                    .into_iter()
+                    .filter(|url| !Self::url_resolves(url, root))
+                    .map(|url| BrokenRef { kind: "importmap", url }),
+            )
+            .collect();
+
+        if broken.is_empty() {
+            Ok(())
+        } else {
+            Err(broken)
+        }
+    }
+
+    fn url_resolves(url: &str, root: &Path) -> bool {
+        root.join(url.trim_start_matches('/')).is_file()
+    }
+
+    /// Build the `{"imports": ...}` JSON value, adding a sibling
+    /// `"integrity"` object when SRI digests are available.
+    #[cfg(feature = "sri")]
+    fn importmap_json(&self, js_map: &BTreeMap<&String, &String>) -> serde_json::Value {
+        let integrity: BTreeMap<_, _> = js_map
+            .values()
+            .filter_map(|url| self.integrity.get(*url).map(|digest| (*url, digest)))
+            .collect();
+
+        if integrity.is_empty() {
+            serde_json::json!({ "imports": js_map })
+        } else {
+            serde_json::json!({ "imports": js_map, "integrity": integrity })
+        }
+    }
+
+    #[cfg(not(feature = "sri"))]
+    fn importmap_json(&self, js_map: &BTreeMap<&String, &String>) -> serde_json::Value {
+        serde_json::json!({ "imports": js_map })
+    }
+
+    /// The ` integrity="sha384-..."` attribute for `url`, or an empty
+    /// string when no SRI digest was computed for it.
+    #[cfg(feature = "sri")]
+    fn integrity_attr(&self, url: &str) -> String {
+        self.integrity
+            .get(url)
+            .map(|digest| format!(r#" integrity="{digest}""#))
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(feature = "sri"))]
+    fn integrity_attr(&self, _url: &str) -> String {
+        String::new()
+    }
+
+    /// Extract the `imports` values from the `<script type="importmap">` block.
+    fn extract_importmap_urls(html: &str) -> Vec<String> {
+        let start = html.find(Self::MARKER_OPEN).unwrap_or(0);
+        let end = html[start..].find(Self::MARKER_CLOSE).map_or(html.len(), |i| start + i);
+        let region = &html[start..end];
+
+        let Some(script_start) = region.find("<script type=\"importmap\">") else {
+            return Vec::new();
+        };
+        let json_start = script_start + "<script type=\"importmap\">".len();
+        let Some(script_end) = region[json_start..].find("</script>") else {
+            return Vec::new();
+        };
+
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&region[json_start..json_start + script_end])
+        else {
+            return Vec::new();
+        };
+
+        value
+            .get("imports")
+            .and_then(|v| v.as_object())
+            .map(|obj| obj.values().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    }
+
     /// Extract href values from link tags with the given rel attribute.
     fn extract_href_values(html: &str, rel: &str) -> Vec<String> {
         let start = html.find(Self::MARKER_OPEN).unwrap_or(0);
@@ -236,3 +533,202 @@ impl ImportMap {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch directory under the system temp dir, unique per test name
+    /// (tests run concurrently in the same process). Caller removes it.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("importmap-test-{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn emit_writes_files_under_their_hashed_name() {
+        let src = temp_dir("emit-writes-files-under-their-hashed-name-src");
+        let out = temp_dir("emit-writes-files-under-their-hashed-name-out");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested/app.js"), b"console.log(1);").unwrap();
+
+        let map = ImportMap::scan(&src, "").unwrap();
+        map.emit(&src, &out).unwrap();
+
+        let entries: Vec<_> = fs::read_dir(out.join("nested"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].starts_with("app.") && entries[0].ends_with(".js"));
+        assert_ne!(entries[0], "app.js");
+        assert_eq!(fs::read(out.join("nested").join(&entries[0])).unwrap(), b"console.log(1);");
+
+        fs::remove_dir_all(&src).unwrap();
+        fs::remove_dir_all(&out).unwrap();
+    }
+
+    #[test]
+    fn scan_finds_every_file_regardless_of_parallel_completion_order() {
+        let dir = temp_dir("scan-finds-every-file-regardless-of-parallel-completion-order");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        for i in 0..20 {
+            fs::write(dir.join("nested").join(format!("mod{i}.js")), format!("export const v = {i};")).unwrap();
+        }
+
+        let map = ImportMap::scan(&dir, "").unwrap();
+
+        assert_eq!(map.len(), 20);
+        for i in 0..20 {
+            assert!(map.contains_key(&format!("/nested/mod{i}.js")));
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scan_output_is_deterministic() {
+        let dir = temp_dir("scan-output-is-deterministic");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        for i in 0..10 {
+            fs::write(dir.join("nested").join(format!("mod{i}.js")), format!("export const v = {i};")).unwrap();
+        }
+
+        let first = ImportMap::scan(&dir, "").unwrap();
+        let second = ImportMap::scan(&dir, "").unwrap();
+        assert_eq!(first, second);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn verify_html_reports_missing_stylesheet_and_modulepreload_refs() {
+        let root = temp_dir("verify-html-reports-missing-stylesheet-and-modulepreload-refs");
+
+        let html = r#"<link rel="stylesheet" href="/missing.css">
+<link rel="modulepreload" href="/missing.js">"#;
+        let broken = ImportMap::empty().verify_html(html, &root).unwrap_err();
+
+        assert_eq!(broken.len(), 2);
+        assert!(broken.iter().any(|b| b.kind == "stylesheet" && b.url == "/missing.css"));
+        assert!(broken.iter().any(|b| b.kind == "modulepreload" && b.url == "/missing.js"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn verify_html_passes_when_every_reference_resolves() {
+        let root = temp_dir("verify-html-passes-when-every-reference-resolves");
+        fs::write(root.join("app.css"), b"").unwrap();
+
+        let html = r#"<link rel="stylesheet" href="/app.css">"#;
+        assert!(ImportMap::empty().verify_html(html, &root).is_ok());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn verify_html_reports_missing_importmap_entries() {
+        let root = temp_dir("verify-html-reports-missing-importmap-entries");
+
+        let html = r#"<script type="importmap">
+{"imports": {"app": "/missing.js"}}
+</script>"#;
+        let broken = ImportMap::empty().verify_html(html, &root).unwrap_err();
+
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].kind, "importmap");
+        assert_eq!(broken[0].url, "/missing.js");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn emit_preserves_subdirectory_structure() {
+        let src = temp_dir("emit-preserves-subdirectory-structure-src");
+        let out = temp_dir("emit-preserves-subdirectory-structure-out");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested/util.js"), b"export const x = 1;").unwrap();
+
+        let map = ImportMap::scan(&src, "").unwrap();
+        map.emit(&src, &out).unwrap();
+
+        let nested_entries: Vec<_> = fs::read_dir(out.join("nested"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(nested_entries.len(), 1);
+        assert!(nested_entries[0].starts_with("util."));
+
+        fs::remove_dir_all(&src).unwrap();
+        fs::remove_dir_all(&out).unwrap();
+    }
+
+    #[test]
+    fn minify_compacts_json_but_keeps_one_tag_per_line() {
+        let src = temp_dir("minify-compacts-json-but-keeps-one-tag-per-line");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested/app.js"), b"console.log(1);").unwrap();
+
+        let map = ImportMap::scan(&src, "").unwrap();
+        let html = format!("{}\n{}", ImportMap::MARKER_OPEN, ImportMap::MARKER_CLOSE);
+
+        let pretty = map.transform_html_with(&html, false).unwrap();
+        let minified = map.transform_html_with(&html, true).unwrap();
+
+        assert!(pretty.contains("{\n"));
+        assert!(!minified.contains("{\n"));
+        assert_eq!(minified.lines().filter(|l| l.contains("<script")).count(), 1);
+
+        fs::remove_dir_all(&src).unwrap();
+    }
+
+    #[test]
+    fn minify_round_trips_multiple_stylesheets() {
+        let src = temp_dir("minify-round-trips-multiple-stylesheets");
+        fs::write(src.join("a.css"), b"a{}").unwrap();
+        fs::write(src.join("b.css"), b"b{}").unwrap();
+
+        let map = ImportMap::scan(&src, "").unwrap();
+        let html = format!(
+            "{}\n<link rel=\"stylesheet\" href=\"/a.css\">\n<link rel=\"stylesheet\" href=\"/b.css\">\n{}",
+            ImportMap::MARKER_OPEN,
+            ImportMap::MARKER_CLOSE
+        );
+
+        let minified = map.transform_html_with(&html, true).unwrap();
+        assert_eq!(minified.matches("rel=\"stylesheet\"").count(), 2);
+
+        // Re-running the transform against its own minified output (the
+        // "next pass" scenario) must still see both stylesheets, not just
+        // the first one on the line.
+        let second_pass = map.transform_html_with(&minified, true).unwrap();
+        assert_eq!(second_pass.matches("rel=\"stylesheet\"").count(), 2);
+
+        fs::remove_dir_all(&src).unwrap();
+    }
+
+    #[cfg(feature = "sri")]
+    #[test]
+    fn integrity_surfaces_as_link_attributes_and_importmap_json() {
+        let src = temp_dir("integrity-surfaces-as-link-attributes-and-importmap-json");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested/app.js"), b"console.log(1);").unwrap();
+
+        let map = ImportMapBuilder::default()
+            .integrity(IntegrityAlgorithm::Sha384)
+            .scan(&src, "")
+            .unwrap();
+
+        let html = format!("{}\n{}", ImportMap::MARKER_OPEN, ImportMap::MARKER_CLOSE);
+        let updated = map.transform_html(&html).unwrap();
+
+        assert!(updated.contains(r#"rel="modulepreload""#));
+        assert!(updated.contains(r#"integrity="sha384-"#));
+        assert!(updated.contains(r#""integrity""#));
+
+        fs::remove_dir_all(&src).unwrap();
+    }
+}
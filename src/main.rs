@@ -6,23 +6,42 @@ fn main() {
     let args: Vec<_> = env::args().skip(1).collect();
 
     if args.iter().any(|a| a == "-h" || a == "--help") {
-        eprintln!("Usage: importmap [dir]");
+        eprintln!("Usage: importmap [dir] [-o|--output-dir <out>] [--check] [--minify]");
         eprintln!();
-        eprintln!("  dir  Directory with index.html (default: .)");
+        eprintln!("  dir                     Directory with index.html (default: .)");
+        eprintln!("  -o, --output-dir <out>  Copy scanned files to <out> under their hashed names");
+        eprintln!("  --check                 Exit non-zero if any referenced asset is missing");
+        eprintln!("  --minify                Compact the importmap JSON in the injected block");
         eprintln!();
         eprintln!("Updates content between <!-- IMPORTMAP --> and <!-- /IMPORTMAP --> markers.");
         process::exit(0);
     }
 
-    let dir = args.first().map(|s| s.as_str()).unwrap_or(".");
+    let mut dir = ".";
+    let mut output_dir = None;
+    let mut check = false;
+    let mut minify = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-o" | "--output-dir" => {
+                i += 1;
+                output_dir = args.get(i).map(String::as_str);
+            }
+            "--check" => check = true,
+            "--minify" => minify = true,
+            other => dir = other,
+        }
+        i += 1;
+    }
 
-    if let Err(e) = run(Path::new(dir)) {
+    if let Err(e) = run(Path::new(dir), output_dir.map(Path::new), check, minify) {
         eprintln!("Error: {e}");
         process::exit(1);
     }
 }
 
-fn run(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+fn run(dir: &Path, output_dir: Option<&Path>, check: bool, minify: bool) -> Result<(), Box<dyn std::error::Error>> {
     let html_path = dir.join("index.html");
     if !html_path.exists() {
         return Err(format!("{} not found", html_path.display()).into());
@@ -30,10 +49,25 @@ fn run(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
 
     let map = ImportMap::scan(dir, "")?;
 
+    if let Some(out_dir) = output_dir {
+        map.emit(dir, out_dir)?;
+        eprintln!("Emitted hashed assets to {}", out_dir.display());
+    }
+
     let html = fs::read_to_string(&html_path)?;
     let updated = map
-        .update_html(&html)
-        .ok_or("Missing <!-- MODULEPRELOAD --> or <!-- IMPORTMAP --> markers")?;
+        .transform_html_with(&html, minify)
+        .ok_or("Missing <!-- IMPORTMAP --> markers")?;
+
+    if check {
+        if let Err(broken) = map.verify_html(&updated, output_dir.unwrap_or(dir)) {
+            for broken_ref in &broken {
+                eprintln!("{broken_ref}");
+            }
+            return Err(format!("{} broken reference(s)", broken.len()).into());
+        }
+    }
+
     fs::write(&html_path, updated)?;
     eprintln!("Updated {}", html_path.display());
 
@@ -0,0 +1,239 @@
+//! Vendor remote ESM dependencies into the import map (`vendor` feature).
+//!
+//! Mirrors `deno vendor`: given a set of remote entry-point URLs, fetch each
+//! module, follow its relative imports, and localize the whole graph under
+//! a vendor directory. Every vendored file is hashed through
+//! `ImportMapBuilder::hash_file_vendored` — the same cache-busting digest
+//! as locally scanned files, but without the local-project dev/test/
+//! underscore/root-`.js` name heuristics, which don't apply to a
+//! third-party package's own layout (a real dependency can legitimately
+//! live at `pkg/test/helper.js`) and would otherwise leave it un-rewritten
+//! and still pointing at the original CDN URL. The generated import map is
+//! self-hosted and cache-busted with no runtime dependence on a
+//! third-party CDN.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    graph::{self, ModuleGraph},
+    ImportMap,
+};
+
+/// Fetches the raw bytes of a module URL.
+///
+/// A trait (rather than a concrete HTTP client) so tests can supply a
+/// fixture fetcher instead of making real network calls.
+pub trait Fetcher {
+    fn fetch(&self, url: &str) -> io::Result<Vec<u8>>;
+}
+
+/// Fetches modules over real HTTP, via a blocking `ureq` request.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HttpFetcher;
+
+impl Fetcher for HttpFetcher {
+    fn fetch(&self, url: &str) -> io::Result<Vec<u8>> {
+        let response = ureq::get(url).call().map_err(io::Error::other)?;
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut response.into_reader(), &mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+/// Strip a URL down to a filesystem-safe relative path, e.g.
+/// `https://cdn.example/lib@1.2.3/index.js` -> `cdn.example/lib@1.2.3/index.js`.
+fn local_path_for(url: &str) -> PathBuf {
+    let stripped = url.trim_start_matches("https://").trim_start_matches("http://");
+    PathBuf::from(stripped)
+}
+
+/// Resolve a relative specifier against the URL that referenced it.
+fn resolve_remote_specifier(base_url: &str, specifier: &str) -> String {
+    let base = base_url.rsplit_once('/').map_or(base_url, |(dir, _)| dir);
+    let mut segments: Vec<&str> = base.split('/').collect();
+
+    for part in specifier.split('/') {
+        match part {
+            "." | "" => {}
+            ".." => {
+                segments.pop();
+            }
+            other => segments.push(other),
+        }
+    }
+
+    segments.join("/")
+}
+
+impl ImportMap {
+    /// Fetch `entries` and their transitive relative imports, write them
+    /// under `vendor_dir`, and register each as a hashed import-map entry
+    /// keyed by its original (remote) URL.
+    pub fn vendor<F: Fetcher>(
+        &mut self,
+        entries: &[&str],
+        vendor_dir: &Path,
+        base_url: &str,
+        fetcher: &F,
+    ) -> io::Result<()> {
+        let base_url = base_url.trim_end_matches('/');
+
+        // Fetch the whole reachable graph first (network I/O), then hash
+        // and write it in dependency order, same as the local scan path.
+        let mut sources: BTreeMap<PathBuf, String> = BTreeMap::new();
+        let mut url_for_path: BTreeMap<PathBuf, String> = BTreeMap::new();
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<String> = entries.iter().map(|s| s.to_string()).collect();
+
+        while let Some(url) = stack.pop() {
+            if !seen.insert(url.clone()) {
+                continue;
+            }
+
+            let contents = fetcher.fetch(&url)?;
+            let Ok(source) = String::from_utf8(contents) else {
+                continue;
+            };
+
+            for specifier in graph::extract_specifiers(&source) {
+                if specifier.starts_with('.') {
+                    stack.push(resolve_remote_specifier(&url, &specifier));
+                }
+            }
+
+            let path = local_path_for(&url);
+            url_for_path.insert(path.clone(), url);
+            sources.insert(path, source);
+        }
+
+        let module_graph = ModuleGraph::build(&sources);
+        let order = module_graph.toposort()?;
+
+        let ctx = VendorContext { graph: &module_graph, url_for_path: &url_for_path, vendor_dir, base_url };
+        for path in &order {
+            let (Some(source), Some(url)) = (sources.get(path), url_for_path.get(path)) else {
+                continue;
+            };
+            self.insert_vendored(path, url, source, &ctx)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rewrite a vendored module's relative specifiers to the hashed URLs
+    /// of its already-inserted dependencies, write it under `vendor_dir`,
+    /// and record it in the map keyed by its original remote `url`.
+    fn insert_vendored(&mut self, path: &Path, url: &str, source: &str, ctx: &VendorContext) -> io::Result<()> {
+        let mut rewritten = source.to_string();
+
+        for (specifier, resolved) in ctx.graph.dependencies(path) {
+            let Some(dep_url) = ctx.url_for_path.get(resolved) else {
+                continue;
+            };
+            if let Some(hashed) = self.imports.get(dep_url) {
+                rewritten = rewritten.replace(&format!("\"{specifier}\""), &format!("\"{hashed}\""));
+                rewritten = rewritten.replace(&format!("'{specifier}'"), &format!("'{hashed}'"));
+            }
+        }
+
+        let bytes = rewritten.into_bytes();
+        let Some(mut scanned) = self.options.hash_file_vendored(path, &bytes) else {
+            return Ok(());
+        };
+        scanned.rewritten = Some(bytes.clone());
+
+        let dest_relative = match scanned.relative_path.parent().filter(|p| *p != Path::new("")) {
+            Some(parent) => parent.join(&scanned.hashed_name),
+            None => PathBuf::from(&scanned.hashed_name),
+        };
+        let dest = ctx.vendor_dir.join(dest_relative);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&dest, &bytes)?;
+
+        self.insert_file(url.to_string(), scanned, ctx.base_url);
+        Ok(())
+    }
+}
+
+/// Per-call context shared across every module inserted by one [`ImportMap::vendor`] call.
+struct VendorContext<'a> {
+    graph: &'a ModuleGraph,
+    url_for_path: &'a BTreeMap<PathBuf, String>,
+    vendor_dir: &'a Path,
+    base_url: &'a str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serves fixed contents from an in-memory map instead of the network.
+    struct FixtureFetcher(BTreeMap<&'static str, &'static str>);
+
+    impl Fetcher for FixtureFetcher {
+        fn fetch(&self, url: &str) -> io::Result<Vec<u8>> {
+            self.0
+                .get(url)
+                .map(|s| s.as_bytes().to_vec())
+                .ok_or_else(|| io::Error::other(format!("no fixture for {url}")))
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("importmap-vendor-test-{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn vendor_rewrites_relative_imports_to_hashed_urls() {
+        let fetcher = FixtureFetcher(BTreeMap::from([
+            ("https://cdn.example/pkg/index.js", "import './util.js';"),
+            ("https://cdn.example/pkg/util.js", "export const x = 1;"),
+        ]));
+        let vendor_dir = temp_dir("vendor-rewrites-relative-imports-to-hashed-urls");
+
+        let mut map = ImportMap::empty();
+        map.vendor(&["https://cdn.example/pkg/index.js"], &vendor_dir, "", &fetcher)
+            .unwrap();
+
+        let hashed_util = map.get("https://cdn.example/pkg/util.js").unwrap();
+        let hashed_index = map.get("https://cdn.example/pkg/index.js").unwrap();
+
+        let index_contents = fs::read_to_string(vendor_dir.join(hashed_index.trim_start_matches('/'))).unwrap();
+        assert!(index_contents.contains(hashed_util));
+        assert!(!index_contents.contains("./util.js"));
+
+        fs::remove_dir_all(&vendor_dir).unwrap();
+    }
+
+    #[test]
+    fn vendor_does_not_drop_files_matching_local_dev_test_heuristics() {
+        // A real vendored path like `pkg/test/helper.js` must not be treated
+        // as a local-project test fixture and silently skipped.
+        let fetcher = FixtureFetcher(BTreeMap::from([
+            ("https://cdn.example/pkg/index.js", "import './test/helper.js';"),
+            ("https://cdn.example/pkg/test/helper.js", "export const helper = 1;"),
+        ]));
+        let vendor_dir = temp_dir("vendor-does-not-drop-files-matching-local-dev-test-heuristics");
+
+        let mut map = ImportMap::empty();
+        map.vendor(&["https://cdn.example/pkg/index.js"], &vendor_dir, "", &fetcher)
+            .unwrap();
+
+        assert!(map.contains_key("https://cdn.example/pkg/test/helper.js"));
+
+        let hashed_index = map.get("https://cdn.example/pkg/index.js").unwrap();
+        let index_contents = fs::read_to_string(vendor_dir.join(hashed_index.trim_start_matches('/'))).unwrap();
+        assert!(!index_contents.contains("./test/helper.js"));
+
+        fs::remove_dir_all(&vendor_dir).unwrap();
+    }
+}